@@ -216,6 +216,249 @@ impl CollectDocScore for MultiCollectorChild {
 }
 
 
+/// Implements `Collector` for tuples of collectors whose arity is known at compile time.
+///
+/// This is a statically typed alternative to `MultiCollector`, for the common case where
+/// the set of collectors to run is known up front. It avoids the boxing and `Downcast`
+/// overhead of `MultiCollector` by fanning out to each member collector directly.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate tantivy;
+/// use tantivy::schema::{SchemaBuilder, TEXT};
+/// use tantivy::{Index, Result};
+/// use tantivy::collector::{CountCollector, TopDocs};
+/// use tantivy::query::QueryParser;
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = SchemaBuilder::new();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let schema = schema_builder.build();
+///     let index = Index::create_in_ram(schema);
+///     {
+///         let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+///         index_writer.add_document(doc!(
+///             title => "The Diary of Muadib",
+///         ));
+///         index_writer.commit().unwrap();
+///     }
+///
+///     index.load_searchers()?;
+///     let searcher = index.searcher();
+///
+///     let query_parser = QueryParser::for_index(&index, vec![title]);
+///     let query = query_parser.parse_query("diary")?;
+///     let (top_docs, count) = searcher.search(
+///         &query,
+///         (TopDocs::with_limit(2), CountCollector::default()),
+///     )?;
+///     assert_eq!(count, 1);
+///     assert_eq!(top_docs.len(), 1);
+///
+///     Ok(())
+/// }
+/// ```
+impl<TCollectorA, TCollectorB> Collector for (TCollectorA, TCollectorB)
+where
+    TCollectorA: Collector,
+    TCollectorB: Collector,
+{
+    type Fruit = (TCollectorA::Fruit, TCollectorB::Fruit);
+    type Child = (TCollectorA::Child, TCollectorB::Child);
+
+    fn for_segment(&self, segment_local_id: SegmentLocalId, reader: &SegmentReader) -> Result<Self::Child> {
+        let child_a = self.0.for_segment(segment_local_id, reader)?;
+        let child_b = self.1.for_segment(segment_local_id, reader)?;
+        Ok((child_a, child_b))
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.0.requires_scoring() || self.1.requires_scoring()
+    }
+
+    fn merge_fruits(&self, children: Vec<Self::Fruit>) -> Self::Fruit {
+        let mut fruits_a = Vec::with_capacity(children.len());
+        let mut fruits_b = Vec::with_capacity(children.len());
+        for (fruit_a, fruit_b) in children {
+            fruits_a.push(fruit_a);
+            fruits_b.push(fruit_b);
+        }
+        (self.0.merge_fruits(fruits_a), self.1.merge_fruits(fruits_b))
+    }
+}
+
+impl<TSegmentCollectorA, TSegmentCollectorB> SegmentCollector for (TSegmentCollectorA, TSegmentCollectorB)
+where
+    TSegmentCollectorA: SegmentCollector,
+    TSegmentCollectorB: SegmentCollector,
+{
+    type Fruit = (TSegmentCollectorA::Fruit, TSegmentCollectorB::Fruit);
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.0.collect(doc, score);
+        self.1.collect(doc, score);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        (self.0.harvest(), self.1.harvest())
+    }
+}
+
+impl<TCollectorA, TCollectorB, TCollectorC> Collector for (TCollectorA, TCollectorB, TCollectorC)
+where
+    TCollectorA: Collector,
+    TCollectorB: Collector,
+    TCollectorC: Collector,
+{
+    type Fruit = (TCollectorA::Fruit, TCollectorB::Fruit, TCollectorC::Fruit);
+    type Child = (TCollectorA::Child, TCollectorB::Child, TCollectorC::Child);
+
+    fn for_segment(&self, segment_local_id: SegmentLocalId, reader: &SegmentReader) -> Result<Self::Child> {
+        let child_a = self.0.for_segment(segment_local_id, reader)?;
+        let child_b = self.1.for_segment(segment_local_id, reader)?;
+        let child_c = self.2.for_segment(segment_local_id, reader)?;
+        Ok((child_a, child_b, child_c))
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.0.requires_scoring() || self.1.requires_scoring() || self.2.requires_scoring()
+    }
+
+    fn merge_fruits(&self, children: Vec<Self::Fruit>) -> Self::Fruit {
+        let mut fruits_a = Vec::with_capacity(children.len());
+        let mut fruits_b = Vec::with_capacity(children.len());
+        let mut fruits_c = Vec::with_capacity(children.len());
+        for (fruit_a, fruit_b, fruit_c) in children {
+            fruits_a.push(fruit_a);
+            fruits_b.push(fruit_b);
+            fruits_c.push(fruit_c);
+        }
+        (
+            self.0.merge_fruits(fruits_a),
+            self.1.merge_fruits(fruits_b),
+            self.2.merge_fruits(fruits_c),
+        )
+    }
+}
+
+impl<TSegmentCollectorA, TSegmentCollectorB, TSegmentCollectorC> SegmentCollector
+    for (TSegmentCollectorA, TSegmentCollectorB, TSegmentCollectorC)
+where
+    TSegmentCollectorA: SegmentCollector,
+    TSegmentCollectorB: SegmentCollector,
+    TSegmentCollectorC: SegmentCollector,
+{
+    type Fruit = (
+        TSegmentCollectorA::Fruit,
+        TSegmentCollectorB::Fruit,
+        TSegmentCollectorC::Fruit,
+    );
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.0.collect(doc, score);
+        self.1.collect(doc, score);
+        self.2.collect(doc, score);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        (self.0.harvest(), self.1.harvest(), self.2.harvest())
+    }
+}
+
+impl<TCollectorA, TCollectorB, TCollectorC, TCollectorD> Collector
+    for (TCollectorA, TCollectorB, TCollectorC, TCollectorD)
+where
+    TCollectorA: Collector,
+    TCollectorB: Collector,
+    TCollectorC: Collector,
+    TCollectorD: Collector,
+{
+    type Fruit = (
+        TCollectorA::Fruit,
+        TCollectorB::Fruit,
+        TCollectorC::Fruit,
+        TCollectorD::Fruit,
+    );
+    type Child = (
+        TCollectorA::Child,
+        TCollectorB::Child,
+        TCollectorC::Child,
+        TCollectorD::Child,
+    );
+
+    fn for_segment(&self, segment_local_id: SegmentLocalId, reader: &SegmentReader) -> Result<Self::Child> {
+        let child_a = self.0.for_segment(segment_local_id, reader)?;
+        let child_b = self.1.for_segment(segment_local_id, reader)?;
+        let child_c = self.2.for_segment(segment_local_id, reader)?;
+        let child_d = self.3.for_segment(segment_local_id, reader)?;
+        Ok((child_a, child_b, child_c, child_d))
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.0.requires_scoring()
+            || self.1.requires_scoring()
+            || self.2.requires_scoring()
+            || self.3.requires_scoring()
+    }
+
+    fn merge_fruits(&self, children: Vec<Self::Fruit>) -> Self::Fruit {
+        let mut fruits_a = Vec::with_capacity(children.len());
+        let mut fruits_b = Vec::with_capacity(children.len());
+        let mut fruits_c = Vec::with_capacity(children.len());
+        let mut fruits_d = Vec::with_capacity(children.len());
+        for (fruit_a, fruit_b, fruit_c, fruit_d) in children {
+            fruits_a.push(fruit_a);
+            fruits_b.push(fruit_b);
+            fruits_c.push(fruit_c);
+            fruits_d.push(fruit_d);
+        }
+        (
+            self.0.merge_fruits(fruits_a),
+            self.1.merge_fruits(fruits_b),
+            self.2.merge_fruits(fruits_c),
+            self.3.merge_fruits(fruits_d),
+        )
+    }
+}
+
+impl<TSegmentCollectorA, TSegmentCollectorB, TSegmentCollectorC, TSegmentCollectorD> SegmentCollector
+    for (
+        TSegmentCollectorA,
+        TSegmentCollectorB,
+        TSegmentCollectorC,
+        TSegmentCollectorD,
+    )
+where
+    TSegmentCollectorA: SegmentCollector,
+    TSegmentCollectorB: SegmentCollector,
+    TSegmentCollectorC: SegmentCollector,
+    TSegmentCollectorD: SegmentCollector,
+{
+    type Fruit = (
+        TSegmentCollectorA::Fruit,
+        TSegmentCollectorB::Fruit,
+        TSegmentCollectorC::Fruit,
+        TSegmentCollectorD::Fruit,
+    );
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.0.collect(doc, score);
+        self.1.collect(doc, score);
+        self.2.collect(doc, score);
+        self.3.collect(doc, score);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        (
+            self.0.harvest(),
+            self.1.harvest(),
+            self.2.harvest(),
+            self.3.harvest(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -225,6 +468,7 @@ mod tests {
     use query::TermQuery;
     use Index;
     use Term;
+    use DocAddress;
     use schema::IndexRecordOption;
 
     /*
@@ -262,4 +506,40 @@ mod tests {
         assert_eq!(count_collector.count(), 5);
     }
     */
+
+    #[test]
+    fn test_tuple_collector_fans_out_to_every_member() {
+        let mut schema_builder = SchemaBuilder::new();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer.add_document(doc!(text=>"abc"));
+            index_writer.add_document(doc!(text=>"abc abc abc"));
+            index_writer.add_document(doc!(text=>"abc abc"));
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text=>""));
+            index_writer.add_document(doc!(text=>"abc abc abc abc"));
+            index_writer.add_document(doc!(text=>"abc"));
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let term = Term::from_field_text(text, "abc");
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+
+        let (top_docs, count): (Vec<(Score, DocAddress)>, usize) = searcher
+            .search(&query, (TopCollector::with_limit(2), CountCollector::default()))
+            .unwrap();
+        assert_eq!(count, 5);
+        assert_eq!(top_docs.len(), 2);
+    }
+
+    #[test]
+    fn test_tuple_collector_requires_scoring_is_the_or_of_its_members() {
+        assert!((TopCollector::<Score>::with_limit(2), CountCollector::default()).requires_scoring());
+        assert!(!(CountCollector::default(), CountCollector::default()).requires_scoring());
+    }
 }
@@ -11,6 +11,7 @@ use DocAddress;
 use collector::TopDocsByField;
 use schema::Field;
 use fastfield::FastValue;
+use std::marker::PhantomData;
 
 /// The Top Score Collector keeps track of the K documents
 /// sorted by their score.
@@ -79,6 +80,227 @@ impl TopDocs {
     pub fn order_by_field<T: PartialOrd + FastValue + Clone>(self, field: Field) -> TopDocsByField<T> {
         TopDocsByField::new(field, self.0.limit())
     }
+
+    /// Ranks the documents using a custom score that is computed by tweaking the original
+    /// relevancy score, typically by combining it with a fast field (e.g. a "recency" boost).
+    ///
+    /// `score_tweaker` is called once per segment and returns a `ScoreSegmentTweaker` that
+    /// receives every collected document's `DocId` along with its original `Score` and returns
+    /// the final `TScore` documents will be ranked on.
+    ///
+    /// ```rust,no_run
+    /// # use tantivy::DocId;
+    /// # use tantivy::Result;
+    /// # use tantivy::Score;
+    /// # use tantivy::SegmentReader;
+    /// # use tantivy::collector::TopDocs;
+    /// # use tantivy::collector::{ScoreSegmentTweaker, ScoreTweaker};
+    /// # use tantivy::schema::Field;
+    /// #
+    /// # fn make_collector(field: Field) -> tantivy::Result<()> {
+    /// struct BoostByField(Field);
+    ///
+    /// struct SegmentBoostByField {
+    ///     fast_field_reader: tantivy::fastfield::FastFieldReader<u64>,
+    /// }
+    ///
+    /// impl ScoreSegmentTweaker<Score> for SegmentBoostByField {
+    ///     fn score(&mut self, doc: DocId, original_score: Score) -> Score {
+    ///         original_score * (1.0 + self.fast_field_reader.get(doc) as Score)
+    ///     }
+    /// }
+    ///
+    /// impl ScoreTweaker<Score> for BoostByField {
+    ///     type Child = SegmentBoostByField;
+    ///
+    ///     fn for_segment(&self, segment_reader: &SegmentReader) -> Result<SegmentBoostByField> {
+    ///         let fast_field_reader = segment_reader.fast_field_reader(self.0)?;
+    ///         Ok(SegmentBoostByField { fast_field_reader })
+    ///     }
+    /// }
+    ///
+    /// let _ = TopDocs::with_limit(10).tweak_score(BoostByField(field));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tweak_score<TScore, TScoreTweaker>(
+        self,
+        score_tweaker: TScoreTweaker,
+    ) -> TopTweakedScoreCollector<TScoreTweaker, TScore>
+    where
+        TScore: 'static + Send + Sync + Clone + PartialOrd,
+        TScoreTweaker: ScoreTweaker<TScore>,
+    {
+        TopTweakedScoreCollector {
+            collector: TopCollector::with_limit(self.0.limit()),
+            score_tweaker,
+        }
+    }
+
+    /// Ranks the documents purely using a `CustomScorer`, ignoring the original relevancy
+    /// score entirely. Because the score only depends on the `DocId`, the resulting collector's
+    /// `requires_scoring()` returns `false`, letting the query skip the (possibly expensive)
+    /// BM25 computation.
+    pub fn custom_score<TScore, TCustomScorer>(
+        self,
+        custom_scorer: TCustomScorer,
+    ) -> TopCustomScoreCollector<TCustomScorer, TScore>
+    where
+        TScore: 'static + Send + Sync + Clone + PartialOrd,
+        TCustomScorer: CustomScorer<TScore>,
+    {
+        TopCustomScoreCollector {
+            collector: TopCollector::with_limit(self.0.limit()),
+            custom_scorer,
+        }
+    }
+}
+
+/// `ScoreSegmentTweaker` is the segment-local counterpart of a `ScoreTweaker`. It is created
+/// once per segment and is then called for every collected document.
+pub trait ScoreSegmentTweaker<TScore>: 'static {
+    /// Tweaks the given document's original score into the final `TScore` documents are
+    /// ranked on.
+    fn score(&mut self, doc: DocId, original_score: Score) -> TScore;
+}
+
+/// `ScoreTweaker` makes it possible to rank documents using a score that combines the
+/// original relevancy `Score` with arbitrary per-segment data, such as a fast field.
+///
+/// See [`TopDocs::tweak_score`](struct.TopDocs.html#method.tweak_score) for an example.
+pub trait ScoreTweaker<TScore>: Sync {
+    /// Type of the segment-local `ScoreSegmentTweaker` associated to this `ScoreTweaker`.
+    type Child: ScoreSegmentTweaker<TScore>;
+
+    /// Builds a `ScoreSegmentTweaker` dedicated to a specific segment.
+    fn for_segment(&self, segment_reader: &SegmentReader) -> Result<Self::Child>;
+}
+
+/// `CustomSegmentScorer` is the segment-local counterpart of a `CustomScorer`. It is created
+/// once per segment and is then called for every collected document.
+pub trait CustomSegmentScorer<TScore>: 'static {
+    /// Computes the score of the given document, ignoring its original relevancy score.
+    fn score(&mut self, doc: DocId) -> TScore;
+}
+
+/// `CustomScorer` makes it possible to rank documents using a score computed purely from
+/// the `DocId`, ignoring relevancy entirely (e.g. sorting by a popularity fast field).
+pub trait CustomScorer<TScore>: Sync {
+    /// Type of the segment-local `CustomSegmentScorer` associated to this `CustomScorer`.
+    type Child: CustomSegmentScorer<TScore>;
+
+    /// Builds a `CustomSegmentScorer` dedicated to a specific segment.
+    fn for_segment(&self, segment_reader: &SegmentReader) -> Result<Self::Child>;
+}
+
+/// Collector built from `TopDocs::tweak_score`.
+pub struct TopTweakedScoreCollector<TScoreTweaker, TScore> {
+    collector: TopCollector<TScore>,
+    score_tweaker: TScoreTweaker,
+}
+
+impl<TScoreTweaker, TScore> Collector for TopTweakedScoreCollector<TScoreTweaker, TScore>
+where
+    TScore: 'static + Send + Sync + Clone + PartialOrd,
+    TScoreTweaker: ScoreTweaker<TScore>,
+{
+    type Fruit = Vec<(TScore, DocAddress)>;
+    type Child = TopTweakedScoreSegmentCollector<TScoreTweaker::Child, TScore>;
+
+    fn for_segment(&self, segment_local_id: SegmentLocalId, reader: &SegmentReader) -> Result<Self::Child> {
+        let segment_collector = self.collector.for_segment(segment_local_id, reader)?;
+        let segment_score_tweaker = self.score_tweaker.for_segment(reader)?;
+        Ok(TopTweakedScoreSegmentCollector {
+            segment_collector,
+            segment_score_tweaker,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, child_fruits: Vec<Vec<(TScore, DocAddress)>>) -> Self::Fruit {
+        self.collector.merge_fruits(child_fruits)
+    }
+}
+
+pub struct TopTweakedScoreSegmentCollector<TSegmentScoreTweaker, TScore> {
+    segment_collector: TopSegmentCollector<TScore>,
+    segment_score_tweaker: TSegmentScoreTweaker,
+}
+
+impl<TSegmentScoreTweaker, TScore> SegmentCollector
+    for TopTweakedScoreSegmentCollector<TSegmentScoreTweaker, TScore>
+where
+    TScore: 'static + Send + Sync + Clone + PartialOrd,
+    TSegmentScoreTweaker: ScoreSegmentTweaker<TScore>,
+{
+    type Fruit = Vec<(TScore, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let tweaked_score = self.segment_score_tweaker.score(doc, score);
+        self.segment_collector.collect(doc, tweaked_score);
+    }
+
+    fn harvest(self) -> Vec<(TScore, DocAddress)> {
+        self.segment_collector.harvest()
+    }
+}
+
+/// Collector built from `TopDocs::custom_score`.
+pub struct TopCustomScoreCollector<TCustomScorer, TScore> {
+    collector: TopCollector<TScore>,
+    custom_scorer: TCustomScorer,
+}
+
+impl<TCustomScorer, TScore> Collector for TopCustomScoreCollector<TCustomScorer, TScore>
+where
+    TScore: 'static + Send + Sync + Clone + PartialOrd,
+    TCustomScorer: CustomScorer<TScore>,
+{
+    type Fruit = Vec<(TScore, DocAddress)>;
+    type Child = TopCustomScoreSegmentCollector<TCustomScorer::Child, TScore>;
+
+    fn for_segment(&self, segment_local_id: SegmentLocalId, reader: &SegmentReader) -> Result<Self::Child> {
+        let segment_collector = self.collector.for_segment(segment_local_id, reader)?;
+        let segment_custom_scorer = self.custom_scorer.for_segment(reader)?;
+        Ok(TopCustomScoreSegmentCollector {
+            segment_collector,
+            segment_custom_scorer,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, child_fruits: Vec<Vec<(TScore, DocAddress)>>) -> Self::Fruit {
+        self.collector.merge_fruits(child_fruits)
+    }
+}
+
+pub struct TopCustomScoreSegmentCollector<TSegmentCustomScorer, TScore> {
+    segment_collector: TopSegmentCollector<TScore>,
+    segment_custom_scorer: TSegmentCustomScorer,
+}
+
+impl<TSegmentCustomScorer, TScore> SegmentCollector
+    for TopCustomScoreSegmentCollector<TSegmentCustomScorer, TScore>
+where
+    TScore: 'static + Send + Sync + Clone + PartialOrd,
+    TSegmentCustomScorer: CustomSegmentScorer<TScore>,
+{
+    type Fruit = Vec<(TScore, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let score = self.segment_custom_scorer.score(doc);
+        self.segment_collector.collect(doc, score);
+    }
+
+    fn harvest(self) -> Vec<(TScore, DocAddress)> {
+        self.segment_collector.harvest()
+    }
 }
 
 
@@ -118,30 +340,133 @@ impl Collector for TopDocs {
 }
 
 
+/// Wraps a collector whose `Fruit` is `Vec<(u64, DocAddress)>` — the raw, order-preserving
+/// fast field encoding — and converts every harvested value to its typed `TFastValue`
+/// (`i64`, `f64`, `DateTime`, ...) once ranking is complete.
+///
+/// Fast fields are always stored as `u64` using an encoding that preserves the original
+/// ordering (e.g. signed integers and floats are bit-flipped so that comparing the raw bits
+/// gives the same order as comparing the typed values). Because of that, ranking can keep
+/// happening on the `u64` representation through the wrapped collector; only the final
+/// result needs to be mapped back to `TFastValue` for the caller. `TopDocs::order_by_field`
+/// does not use this collector: it returns a `TopDocsByField`.
+pub struct FastFieldConvertCollector<TCollector, TFastValue>
+where
+    TCollector: Collector<Fruit = Vec<(u64, DocAddress)>>,
+    TFastValue: FastValue,
+{
+    // Wrapped collector, ranking documents on the raw `u64` fast field representation.
+    collector: TCollector,
+    // Fast field being read. Used to check, at `for_segment` time, that the field's type
+    // in the schema actually matches `TFastValue`.
+    field: Field,
+    fast_value: PhantomData<TFastValue>,
+}
+
+impl<TCollector, TFastValue> FastFieldConvertCollector<TCollector, TFastValue>
+where
+    TCollector: Collector<Fruit = Vec<(u64, DocAddress)>>,
+    TFastValue: FastValue,
+{
+    pub(crate) fn new(collector: TCollector, field: Field) -> FastFieldConvertCollector<TCollector, TFastValue> {
+        FastFieldConvertCollector {
+            collector,
+            field,
+            fast_value: PhantomData,
+        }
+    }
+}
+
+impl<TCollector, TFastValue> Collector for FastFieldConvertCollector<TCollector, TFastValue>
+where
+    TCollector: Collector<Fruit = Vec<(u64, DocAddress)>>,
+    TFastValue: FastValue,
+{
+    type Fruit = Vec<(TFastValue, DocAddress)>;
+    type Child = FastFieldConvertSegmentCollector<TCollector::Child, TFastValue>;
+
+    fn for_segment(&self, segment_local_id: SegmentLocalId, reader: &SegmentReader) -> Result<Self::Child> {
+        let field_entry = reader.schema().get_field_entry(self.field);
+        TFastValue::check_field_type(field_entry.field_type())?;
+        let segment_collector = self.collector.for_segment(segment_local_id, reader)?;
+        Ok(FastFieldConvertSegmentCollector {
+            segment_collector,
+            fast_value: PhantomData,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn merge_fruits(&self, children: Vec<Vec<(u64, DocAddress)>>) -> Self::Fruit {
+        self.collector
+            .merge_fruits(children)
+            .into_iter()
+            .map(|(val, doc_address)| (TFastValue::from_u64(val), doc_address))
+            .collect()
+    }
+}
+
+pub struct FastFieldConvertSegmentCollector<TSegmentCollector, TFastValue> {
+    segment_collector: TSegmentCollector,
+    fast_value: PhantomData<TFastValue>,
+}
+
+impl<TSegmentCollector, TFastValue> SegmentCollector
+    for FastFieldConvertSegmentCollector<TSegmentCollector, TFastValue>
+where
+    TSegmentCollector: SegmentCollector<Fruit = Vec<(u64, DocAddress)>>,
+    TFastValue: FastValue,
+{
+    type Fruit = Vec<(TFastValue, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.segment_collector.collect(doc, score);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.segment_collector
+            .harvest()
+            .into_iter()
+            .map(|(val, doc_address)| (TFastValue::from_u64(val), doc_address))
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // TODO fix tests
 
     use super::TopDocs;
-    use collector::SegmentCollector;
+    use super::{CustomScorer, CustomSegmentScorer, ScoreTweaker, ScoreSegmentTweaker};
+    use super::FastFieldConvertCollector;
+    use collector::{Collector, SegmentCollector};
+    use DocId;
+    use Result;
     use Score;
+    use SegmentLocalId;
+    use SegmentReader;
     use schema::SchemaBuilder;
     use Index;
-    use schema::TEXT;
+    use schema::{TEXT, FAST};
     use query::QueryParser;
     use DocAddress;
+    use schema::Field;
+    use fastfield::FastFieldReader;
 
     fn make_index() -> Index {
         let mut schema_builder = SchemaBuilder::default();
         let text_field = schema_builder.add_text_field("text", TEXT);
+        let rank_field = schema_builder.add_u64_field("rank", FAST);
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         {
             // writing the segment
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.add_document(doc!(text_field=>"Hello happy tax payer."));
-            index_writer.add_document(doc!(text_field=>"Droopy says hello happy tax payer"));
-            index_writer.add_document(doc!(text_field=>"I like Droopy"));
+            index_writer.add_document(doc!(text_field=>"Hello happy tax payer.", rank_field=>0u64));
+            index_writer.add_document(doc!(text_field=>"Droopy says hello happy tax payer", rank_field=>1u64));
+            index_writer.add_document(doc!(text_field=>"I like Droopy", rank_field=>2u64));
             assert!(index_writer.commit().is_ok());
         }
         index.load_searchers().unwrap();
@@ -183,5 +508,205 @@ mod tests {
         TopDocs::with_limit(0);
     }
 
+    #[test]
+    fn test_tweak_score_inverts_ranking() {
+        let index = make_index();
+        let field = index.schema().get_field("text").unwrap();
+        let query_parser = QueryParser::for_index(&index, vec![field]);
+        let text_query = query_parser.parse_query("droopy tax").unwrap();
+
+        struct InvertScore;
+        struct InvertScoreSegment;
+
+        impl ScoreSegmentTweaker<Score> for InvertScoreSegment {
+            fn score(&mut self, _doc: DocId, original_score: Score) -> Score {
+                -original_score
+            }
+        }
+
+        impl ScoreTweaker<Score> for InvertScore {
+            type Child = InvertScoreSegment;
+
+            fn for_segment(&self, _segment_reader: &SegmentReader) -> Result<InvertScoreSegment> {
+                Ok(InvertScoreSegment)
+            }
+        }
+
+        let score_docs: Vec<(Score, DocAddress)> = index
+            .searcher()
+            .search(&text_query, TopDocs::with_limit(4).tweak_score(InvertScore))
+            .unwrap();
+        assert_eq!(score_docs, vec![
+            (-0.48527452, DocAddress(0, 0)),
+            (-0.5376842, DocAddress(0u32, 2)),
+            (-0.81221175, DocAddress(0u32, 1)),
+        ]);
+    }
+
+    #[test]
+    fn test_custom_score_orders_by_doc_id_without_scoring() {
+        let index = make_index();
+        let field = index.schema().get_field("text").unwrap();
+        let query_parser = QueryParser::for_index(&index, vec![field]);
+        let text_query = query_parser.parse_query("droopy tax").unwrap();
+
+        struct DocIdScorer;
+        struct DocIdSegmentScorer;
+
+        impl CustomSegmentScorer<DocId> for DocIdSegmentScorer {
+            fn score(&mut self, doc: DocId) -> DocId {
+                doc
+            }
+        }
+
+        impl CustomScorer<DocId> for DocIdScorer {
+            type Child = DocIdSegmentScorer;
+
+            fn for_segment(&self, _segment_reader: &SegmentReader) -> Result<DocIdSegmentScorer> {
+                Ok(DocIdSegmentScorer)
+            }
+        }
+
+        let collector = TopDocs::with_limit(4).custom_score(DocIdScorer);
+        assert!(!collector.requires_scoring());
+        let doc_id_docs: Vec<(DocId, DocAddress)> =
+            index.searcher().search(&text_query, collector).unwrap();
+        assert_eq!(doc_id_docs, vec![
+            (2, DocAddress(0, 2)),
+            (1, DocAddress(0, 1)),
+            (0, DocAddress(0, 0)),
+        ]);
+    }
+
+    #[test]
+    fn test_fast_field_convert_collector_converts_u64_values() {
+        let index = make_index();
+        let text_field = index.schema().get_field("text").unwrap();
+        let rank_field = index.schema().get_field("rank").unwrap();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let text_query = query_parser.parse_query("droopy tax").unwrap();
+
+        struct DocIdAsU64Collector;
+        struct DocIdAsU64SegmentCollector(SegmentLocalId, Vec<(u64, DocAddress)>);
+
+        impl SegmentCollector for DocIdAsU64SegmentCollector {
+            type Fruit = Vec<(u64, DocAddress)>;
+
+            fn collect(&mut self, doc: DocId, _score: Score) {
+                self.1.push((u64::from(doc), DocAddress(self.0, doc)));
+            }
+
+            fn harvest(self) -> Self::Fruit {
+                self.1
+            }
+        }
+
+        impl Collector for DocIdAsU64Collector {
+            type Fruit = Vec<(u64, DocAddress)>;
+            type Child = DocIdAsU64SegmentCollector;
+
+            fn for_segment(
+                &self,
+                segment_local_id: SegmentLocalId,
+                _reader: &SegmentReader,
+            ) -> Result<Self::Child> {
+                Ok(DocIdAsU64SegmentCollector(segment_local_id, Vec::new()))
+            }
+
+            fn requires_scoring(&self) -> bool {
+                false
+            }
+
+            fn merge_fruits(&self, children: Vec<Vec<(u64, DocAddress)>>) -> Self::Fruit {
+                children.into_iter().flatten().collect()
+            }
+        }
+
+        let collector: FastFieldConvertCollector<DocIdAsU64Collector, u64> =
+            FastFieldConvertCollector::new(DocIdAsU64Collector, rank_field);
+        let results = index.searcher().search(&text_query, collector).unwrap();
+        let mut doc_ids: Vec<u64> = results.iter().map(|&(val, _)| val).collect();
+        doc_ids.sort();
+        assert_eq!(doc_ids, vec![0u64, 1u64, 2u64]);
+        for &(val, DocAddress(_, doc)) in &results {
+            assert_eq!(val, u64::from(doc));
+        }
+    }
+
+    #[test]
+    fn test_fast_field_convert_collector_decodes_signed_values() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let score_field = schema_builder.add_i64_field("score", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.add_document(doc!(text_field=>"droopy", score_field=>-42i64));
+            index_writer.add_document(doc!(text_field=>"droopy", score_field=>0i64));
+            index_writer.add_document(doc!(text_field=>"droopy", score_field=>17i64));
+            assert!(index_writer.commit().is_ok());
+        }
+        index.load_searchers().unwrap();
+
+        // Reads the field's raw, order-preserving `u64` encoding directly off the fast field
+        // reader. `FastFieldConvertCollector` must then decode that back into the original signed
+        // `i64`, not just pass the `u64` bit pattern through.
+        struct RawFastFieldCollector(Field);
+        struct RawFastFieldSegmentCollector {
+            segment_local_id: SegmentLocalId,
+            fast_field_reader: FastFieldReader<u64>,
+            fruit: Vec<(u64, DocAddress)>,
+        }
+
+        impl SegmentCollector for RawFastFieldSegmentCollector {
+            type Fruit = Vec<(u64, DocAddress)>;
+
+            fn collect(&mut self, doc: DocId, _score: Score) {
+                let val = self.fast_field_reader.get(doc);
+                self.fruit.push((val, DocAddress(self.segment_local_id, doc)));
+            }
+
+            fn harvest(self) -> Self::Fruit {
+                self.fruit
+            }
+        }
+
+        impl Collector for RawFastFieldCollector {
+            type Fruit = Vec<(u64, DocAddress)>;
+            type Child = RawFastFieldSegmentCollector;
+
+            fn for_segment(
+                &self,
+                segment_local_id: SegmentLocalId,
+                reader: &SegmentReader,
+            ) -> Result<Self::Child> {
+                let fast_field_reader = reader.fast_field_reader(self.0)?;
+                Ok(RawFastFieldSegmentCollector {
+                    segment_local_id,
+                    fast_field_reader,
+                    fruit: Vec::new(),
+                })
+            }
+
+            fn requires_scoring(&self) -> bool {
+                false
+            }
+
+            fn merge_fruits(&self, children: Vec<Vec<(u64, DocAddress)>>) -> Self::Fruit {
+                children.into_iter().flatten().collect()
+            }
+        }
+
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("droopy").unwrap();
+        let collector: FastFieldConvertCollector<RawFastFieldCollector, i64> =
+            FastFieldConvertCollector::new(RawFastFieldCollector(score_field), score_field);
+        let results = index.searcher().search(&query, collector).unwrap();
+
+        let mut scores: Vec<i64> = results.iter().map(|&(val, _)| val).collect();
+        scores.sort();
+        assert_eq!(scores, vec![-42i64, 0i64, 17i64]);
+    }
 }
 
@@ -0,0 +1,234 @@
+use std::io;
+use bitpacking::{BitPacker4x, BitPacker};
+use positions::COMPRESSION_BLOCK_SIZE;
+use positions::serializer::CompressionType;
+use xxhash_rust::xxh3::xxh3_64;
+
+lazy_static! {
+    static ref BIT_PACKER: BitPacker4x = BitPacker4x::new();
+}
+
+/// Reads back the blocks written by a `PositionSerializer`, dispatching on the per-block
+/// codec (when the stream was written with `PositionSerializer::new_with_compression`) and
+/// verifying the optional xxh3 checksum (when written with
+/// `PositionSerializer::new_with_checksums`) each time a block is read with `read_block`.
+pub struct PositionBlockReader {
+    tagged: bool,
+    checksummed: bool,
+}
+
+impl PositionBlockReader {
+    /// `tagged` and `checksummed` must match how the stream being read was serialized:
+    /// `tagged` is `true` only for streams built with `PositionSerializer::new_with_compression`,
+    /// `checksummed` is `true` only for streams built with `PositionSerializer::new_with_checksums`.
+    pub fn new(tagged: bool, checksummed: bool) -> PositionBlockReader {
+        PositionBlockReader { tagged, checksummed }
+    }
+
+    /// Parses one block's skiplist record out of `skiplist`, returning the codec used
+    /// (always `CompressionType::BitPacked` for an untagged stream), the per-codec metadata
+    /// (`num_bits` for `BitPacked`, the compressed payload length otherwise), and how many
+    /// bytes of `skiplist` the record consumed.
+    pub fn read_skiplist_record(&self, skiplist: &[u8]) -> io::Result<(CompressionType, u32, usize)> {
+        let mut pos = 0;
+        let compression_type = if self.tagged {
+            let compression_type = CompressionType::from_code(skiplist[pos])?;
+            pos += 1;
+            compression_type
+        } else {
+            CompressionType::BitPacked
+        };
+        let (metadata, metadata_len) = match compression_type {
+            CompressionType::BitPacked => (u32::from(skiplist[pos]), 1),
+            CompressionType::None | CompressionType::Lz4 => {
+                let mut len_bytes = [0u8; 4];
+                len_bytes.copy_from_slice(&skiplist[pos..pos + 4]);
+                (u32::from_le_bytes(len_bytes), 4)
+            }
+        };
+        Ok((compression_type, metadata, pos + metadata_len))
+    }
+
+    /// Decodes one block's payload into `output`, verifying `checksum` first when this
+    /// reader was built with `checksummed = true`.
+    pub fn read_block(
+        &self,
+        compression_type: CompressionType,
+        metadata: u32,
+        payload: &[u8],
+        checksum: Option<u64>,
+        output: &mut [u32],
+    ) -> io::Result<()> {
+        debug_assert_eq!(output.len(), COMPRESSION_BLOCK_SIZE);
+        if self.checksummed {
+            if let Some(expected) = checksum {
+                if xxh3_64(payload) != expected {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "position block failed its xxh3 checksum",
+                    ));
+                }
+            }
+        }
+        match compression_type {
+            CompressionType::BitPacked => {
+                BIT_PACKER.decompress(payload, output, metadata as u8);
+            }
+            CompressionType::None => {
+                for (i, chunk) in payload.chunks_exact(4).enumerate() {
+                    let mut val_bytes = [0u8; 4];
+                    val_bytes.copy_from_slice(chunk);
+                    output[i] = u32::from_le_bytes(val_bytes);
+                }
+            }
+            CompressionType::Lz4 => {
+                let decompressed = lz4::block::decompress(payload, Some((COMPRESSION_BLOCK_SIZE * 4) as i32))
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+                for (i, chunk) in decompressed.chunks_exact(4).enumerate() {
+                    let mut val_bytes = [0u8; 4];
+                    val_bytes.copy_from_slice(chunk);
+                    output[i] = u32::from_le_bytes(val_bytes);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use positions::serializer::PositionSerializer;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.borrow_mut().flush()
+        }
+    }
+
+    // `PositionSerializer::close` always writes the checksum section last: one `u64` per
+    // block, trailed by its own count. That means reading a given block's checksum back only
+    // requires knowing how many trailing bytes the section occupies, not the layout of
+    // anything that precedes it (long skips, skiplist records, ...).
+    fn nth_checksum(skiplist: &[u8], index: usize) -> u64 {
+        let len = skiplist.len();
+        let mut count_bytes = [0u8; 4];
+        count_bytes.copy_from_slice(&skiplist[len - 4..]);
+        let count = u32::from_le_bytes(count_bytes) as usize;
+        let start = len - 4 - count * 8;
+        let mut checksum_bytes = [0u8; 8];
+        checksum_bytes.copy_from_slice(&skiplist[start + index * 8..start + index * 8 + 8]);
+        u64::from_le_bytes(checksum_bytes)
+    }
+
+    fn write_one_checksummed_block(vals: &[u32]) -> (Vec<u8>, Vec<u8>) {
+        let stream = SharedBuffer::default();
+        let skiplist = SharedBuffer::default();
+        let mut serializer = PositionSerializer::new_with_checksums(stream.clone(), skiplist.clone());
+        serializer.write_all(vals).unwrap();
+        serializer.close().unwrap();
+        (stream.0.borrow().clone(), skiplist.0.borrow().clone())
+    }
+
+    fn write_one_block_with_compression(
+        compression_type: CompressionType,
+        checksummed: bool,
+        vals: &[u32],
+    ) -> (Vec<u8>, Vec<u8>) {
+        let stream = SharedBuffer::default();
+        let skiplist = SharedBuffer::default();
+        let mut serializer = if checksummed {
+            PositionSerializer::new_with_compression_and_checksums(stream.clone(), skiplist.clone(), compression_type)
+        } else {
+            PositionSerializer::new_with_compression(stream.clone(), skiplist.clone(), compression_type)
+        };
+        serializer.write_all(vals).unwrap();
+        serializer.close().unwrap();
+        (stream.0.borrow().clone(), skiplist.0.borrow().clone())
+    }
+
+    #[test]
+    fn test_checksum_round_trip_succeeds() {
+        let vals: Vec<u32> = (0..COMPRESSION_BLOCK_SIZE as u32).collect();
+        let (stream, skiplist) = write_one_checksummed_block(&vals);
+
+        let reader = PositionBlockReader::new(false, true);
+        let (compression_type, metadata, _record_len) = reader.read_skiplist_record(&skiplist).unwrap();
+        let checksum = nth_checksum(&skiplist, 0);
+
+        let mut output = vec![0u32; COMPRESSION_BLOCK_SIZE];
+        reader
+            .read_block(compression_type, metadata, &stream, Some(checksum), &mut output)
+            .unwrap();
+        assert_eq!(output, vals);
+    }
+
+    #[test]
+    fn test_checksum_round_trip_detects_corruption() {
+        let vals: Vec<u32> = (0..COMPRESSION_BLOCK_SIZE as u32).collect();
+        let (mut stream, skiplist) = write_one_checksummed_block(&vals);
+        stream[0] ^= 0xff;
+
+        let reader = PositionBlockReader::new(false, true);
+        let (compression_type, metadata, _record_len) = reader.read_skiplist_record(&skiplist).unwrap();
+        let checksum = nth_checksum(&skiplist, 0);
+
+        let mut output = vec![0u32; COMPRESSION_BLOCK_SIZE];
+        let result = reader.read_block(compression_type, metadata, &stream, Some(checksum), &mut output);
+        assert!(result.is_err());
+    }
+
+    fn assert_compression_round_trips(compression_type: CompressionType) {
+        let vals: Vec<u32> = (0..COMPRESSION_BLOCK_SIZE as u32).map(|i| i * 37).collect();
+        let (stream, skiplist) = write_one_block_with_compression(compression_type, false, &vals);
+
+        let reader = PositionBlockReader::new(true, false);
+        let (decoded_type, metadata, _record_len) = reader.read_skiplist_record(&skiplist).unwrap();
+        assert_eq!(decoded_type, compression_type);
+
+        let mut output = vec![0u32; COMPRESSION_BLOCK_SIZE];
+        reader
+            .read_block(decoded_type, metadata, &stream, None, &mut output)
+            .unwrap();
+        assert_eq!(output, vals);
+    }
+
+    #[test]
+    fn test_compression_type_none_round_trips() {
+        assert_compression_round_trips(CompressionType::None);
+    }
+
+    #[test]
+    fn test_compression_type_lz4_round_trips() {
+        assert_compression_round_trips(CompressionType::Lz4);
+    }
+
+    #[test]
+    fn test_compression_type_and_checksums_compose() {
+        let vals: Vec<u32> = (0..COMPRESSION_BLOCK_SIZE as u32).map(|i| i * 37).collect();
+        let (stream, skiplist) = write_one_block_with_compression(CompressionType::Lz4, true, &vals);
+
+        let reader = PositionBlockReader::new(true, true);
+        let (decoded_type, metadata, _record_len) = reader.read_skiplist_record(&skiplist).unwrap();
+        let checksum = nth_checksum(&skiplist, 0);
+
+        let mut output = vec![0u32; COMPRESSION_BLOCK_SIZE];
+        reader
+            .read_block(decoded_type, metadata, &stream, Some(checksum), &mut output)
+            .unwrap();
+        assert_eq!(output, vals);
+    }
+
+    #[test]
+    fn test_compression_type_from_code_rejects_unknown_byte() {
+        assert!(CompressionType::from_code(255).is_err());
+    }
+}
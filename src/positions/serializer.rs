@@ -1,12 +1,55 @@
 use std::io;
+use std::mem;
 use bitpacking::{BitPacker4x, BitPacker};
 use positions::{COMPRESSION_BLOCK_SIZE, LONG_SKIP_INTERVAL};
 use common::BinarySerializable;
+use xxhash_rust::xxh3::xxh3_64;
 
 lazy_static! {
     static ref BIT_PACKER: BitPacker4x = BitPacker4x::new();
 }
 
+/// Per-block compression codec for the position stream.
+///
+/// Bit-packing gives the best ratio for the common case of small, regular position deltas,
+/// but can be a poor fit for large or near-random positions, or for streams made up mostly
+/// of tiny blocks. `CompressionType` lets a segment pick the codec that best matches its
+/// position distribution, the same way LSM engines tag each block with its own compression
+/// type.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompressionType {
+    /// `BitPacker4x` bit-packing. One `num_bits` byte is written to the skiplist per block.
+    BitPacked,
+    /// Raw little-endian `u32` store. No `num_bits`; the skiplist records the compressed
+    /// (here, uncompressed) length instead.
+    None,
+    /// General-purpose LZ4 block compression. Like `None`, the skiplist records the
+    /// compressed length rather than a `num_bits`.
+    Lz4,
+}
+
+impl CompressionType {
+    fn to_code(self) -> u8 {
+        match self {
+            CompressionType::BitPacked => 0u8,
+            CompressionType::None => 1u8,
+            CompressionType::Lz4 => 2u8,
+        }
+    }
+
+    pub(crate) fn from_code(code: u8) -> io::Result<CompressionType> {
+        match code {
+            0u8 => Ok(CompressionType::BitPacked),
+            1u8 => Ok(CompressionType::None),
+            2u8 => Ok(CompressionType::Lz4),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown position block codec id {}", code),
+            )),
+        }
+    }
+}
+
 pub struct PositionSerializer<W: io::Write> {
     write_stream: W,
     write_skiplist: W,
@@ -15,6 +58,15 @@ pub struct PositionSerializer<W: io::Write> {
     num_ints: u64,
     long_skips: Vec<u64>,
     cumulated_num_bits: u64,
+    // `Some` once block checksums are enabled (see `new_with_checksums`), accumulating one
+    // xxh3 hash per compressed block, written out as a trailing section in `close`.
+    checksums: Option<Vec<u64>>,
+    compression_type: CompressionType,
+    // Only `true` when built via `new_with_compression`. Keeps `new`/`new_with_checksums`
+    // emitting the original, untagged one-byte-per-block skiplist layout so existing readers
+    // don't misparse block boundaries on segments written after `CompressionType` landed;
+    // only callers that explicitly opt into per-block codec selection pay for the tag byte.
+    tagged_blocks: bool,
 }
 
 impl<W: io::Write> PositionSerializer<W> {
@@ -27,6 +79,63 @@ impl<W: io::Write> PositionSerializer<W> {
             num_ints: 0u64,
             long_skips: Vec::new(),
             cumulated_num_bits: 0u64,
+            checksums: None,
+            compression_type: CompressionType::BitPacked,
+            tagged_blocks: false,
+        }
+    }
+
+    /// Like `new`, but additionally hashes every compressed block (xxh3, 64-bit) as it is
+    /// emitted. The accumulated checksums are written as a trailing section after the
+    /// skiplist in `close`, so a matching reader can verify each block when it reads it.
+    /// This costs one extra hash per `COMPRESSION_BLOCK_SIZE` block at write time; indices
+    /// written without this flag simply carry no checksum section.
+    ///
+    /// Checksums and `CompressionType` are independent knobs; use
+    /// `new_with_compression_and_checksums` to opt into both on the same stream.
+    pub fn new_with_checksums(write_stream: W, write_skiplist: W) -> PositionSerializer<W> {
+        PositionSerializer {
+            checksums: Some(Vec::new()),
+            ..PositionSerializer::new(write_stream, write_skiplist)
+        }
+    }
+
+    /// Like `new`, but compresses every block with `compression_type` instead of always
+    /// bit-packing, and tags each block's skiplist record with its codec id so a reader can
+    /// dispatch. Pick `CompressionType::None` or `CompressionType::Lz4` for segments whose
+    /// positions are large or near-random, where bit-packing buys little.
+    ///
+    /// This changes the on-disk skiplist layout (one extra codec-id byte per block), so it
+    /// is opt-in: only streams built through this constructor carry the tag. `new` and
+    /// `new_with_checksums` keep writing the original, untagged format.
+    ///
+    /// This does not enable checksums; use `new_with_compression_and_checksums` for both.
+    pub fn new_with_compression(
+        write_stream: W,
+        write_skiplist: W,
+        compression_type: CompressionType,
+    ) -> PositionSerializer<W> {
+        PositionSerializer {
+            compression_type,
+            tagged_blocks: true,
+            ..PositionSerializer::new(write_stream, write_skiplist)
+        }
+    }
+
+    /// Combines `new_with_compression` and `new_with_checksums`: every block is compressed
+    /// with `compression_type`, tagged with its codec id, and hashed (xxh3) for later
+    /// verification. `new_with_checksums` and `new_with_compression` can't be composed by
+    /// calling one after the other — each is built from `new` via struct-update syntax, so
+    /// one resets the other's field back to its default — so segments that want both
+    /// properties together need this constructor instead.
+    pub fn new_with_compression_and_checksums(
+        write_stream: W,
+        write_skiplist: W,
+        compression_type: CompressionType,
+    ) -> PositionSerializer<W> {
+        PositionSerializer {
+            checksums: Some(Vec::new()),
+            ..PositionSerializer::new_with_compression(write_stream, write_skiplist, compression_type)
         }
     }
 
@@ -43,21 +152,86 @@ impl<W: io::Write> PositionSerializer<W> {
         Ok(())
     }
 
-    pub fn write_all(&mut self, vals: &[u32]) -> io::Result<()> {
-        // TODO optimize
-        for &val in vals {
-            self.write(val)?;
+    pub fn write_all(&mut self, mut vals: &[u32]) -> io::Result<()> {
+        // Top up the block already in progress, flushing it once it reaches
+        // `COMPRESSION_BLOCK_SIZE`.
+        if !self.block.is_empty() {
+            let num_to_fill = (COMPRESSION_BLOCK_SIZE - self.block.len()).min(vals.len());
+            self.block.extend_from_slice(&vals[..num_to_fill]);
+            self.num_ints += num_to_fill as u64;
+            vals = &vals[num_to_fill..];
+            if self.block.len() == COMPRESSION_BLOCK_SIZE {
+                self.flush_block()?;
+            }
         }
+        // Bulk-compress whole blocks straight out of `vals`, without ever copying them
+        // into `self.block` first.
+        while vals.len() >= COMPRESSION_BLOCK_SIZE {
+            let block = &vals[..COMPRESSION_BLOCK_SIZE];
+            self.num_ints += COMPRESSION_BLOCK_SIZE as u64;
+            self.compress_and_emit(block)?;
+            vals = &vals[COMPRESSION_BLOCK_SIZE..];
+        }
+        // Stash the tail (fewer than `COMPRESSION_BLOCK_SIZE` values) for the next
+        // `write`/`write_all`/`close`.
+        self.block.extend_from_slice(vals);
+        self.num_ints += vals.len() as u64;
         Ok(())
     }
 
     fn flush_block(&mut self) -> io::Result<()> {
-        let num_bits = BIT_PACKER.num_bits(&self.block[..]);
-        self.cumulated_num_bits += num_bits as u64;
-        self.write_skiplist.write(&[num_bits])?;
-        let written_len = BIT_PACKER.compress(&self.block[..], &mut self.buffer, num_bits);
+        let block = mem::replace(&mut self.block, Vec::with_capacity(COMPRESSION_BLOCK_SIZE));
+        self.compress_and_emit(&block)
+    }
+
+    /// Compresses a full `COMPRESSION_BLOCK_SIZE` block with `self.compression_type`, writes
+    /// its skiplist entry (codec id when `tagged_blocks` is set, then `num_bits` or compressed
+    /// length), and appends it to
+    /// `write_stream`. Assumes `self.num_ints` already accounts for `block`.
+    fn compress_and_emit(&mut self, block: &[u32]) -> io::Result<()> {
+        debug_assert_eq!(block.len(), COMPRESSION_BLOCK_SIZE);
+        if self.tagged_blocks {
+            self.write_skiplist.write(&[self.compression_type.to_code()])?;
+        }
+        let written_len = match self.compression_type {
+            CompressionType::BitPacked => {
+                let num_bits = BIT_PACKER.num_bits(block);
+                self.cumulated_num_bits += num_bits as u64;
+                self.write_skiplist.write(&[num_bits])?;
+                BIT_PACKER.compress(block, &mut self.buffer, num_bits)
+            }
+            CompressionType::None => {
+                let written_len = block.len() * 4;
+                if self.buffer.len() < written_len {
+                    self.buffer.resize(written_len, 0u8);
+                }
+                for (i, &val) in block.iter().enumerate() {
+                    self.buffer[i * 4..(i + 1) * 4].copy_from_slice(&val.to_le_bytes());
+                }
+                self.cumulated_num_bits += (written_len * 8) as u64;
+                (written_len as u32).serialize(&mut self.write_skiplist)?;
+                written_len
+            }
+            CompressionType::Lz4 => {
+                let mut raw = vec![0u8; block.len() * 4];
+                for (i, &val) in block.iter().enumerate() {
+                    raw[i * 4..(i + 1) * 4].copy_from_slice(&val.to_le_bytes());
+                }
+                let compressed = lz4::block::compress(&raw, None, false)
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+                if self.buffer.len() < compressed.len() {
+                    self.buffer.resize(compressed.len(), 0u8);
+                }
+                self.buffer[..compressed.len()].copy_from_slice(&compressed);
+                self.cumulated_num_bits += (compressed.len() * 8) as u64;
+                (compressed.len() as u32).serialize(&mut self.write_skiplist)?;
+                compressed.len()
+            }
+        };
+        if let Some(ref mut checksums) = self.checksums {
+            checksums.push(xxh3_64(&self.buffer[..written_len]));
+        }
         self.write_stream.write_all(&self.buffer[..written_len])?;
-        self.block.clear();
         if (self.num_ints % LONG_SKIP_INTERVAL) == 0u64 {
             self.long_skips.push(self.cumulated_num_bits);
         }
@@ -73,8 +247,104 @@ impl<W: io::Write> PositionSerializer<W> {
             long_skip.serialize(&mut self.write_skiplist)?;
         }
         (self.long_skips.len() as u32).serialize(&mut self.write_skiplist)?;
+        if let Some(checksums) = self.checksums {
+            for &checksum in &checksums {
+                checksum.serialize(&mut self.write_skiplist)?;
+            }
+            (checksums.len() as u32).serialize(&mut self.write_skiplist)?;
+        }
         self.write_skiplist.flush()?;
         self.write_stream.flush()?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.borrow_mut().flush()
+        }
+    }
+
+    fn serialize_one_at_a_time(vals: &[u32]) -> (Vec<u8>, Vec<u8>) {
+        let stream = SharedBuffer::default();
+        let skiplist = SharedBuffer::default();
+        let mut serializer = PositionSerializer::new(stream.clone(), skiplist.clone());
+        for &val in vals {
+            serializer.write(val).unwrap();
+        }
+        serializer.close().unwrap();
+        (stream.0.borrow().clone(), skiplist.0.borrow().clone())
+    }
+
+    fn serialize_write_all(vals: &[u32], write_all_chunks: &[usize]) -> (Vec<u8>, Vec<u8>) {
+        let stream = SharedBuffer::default();
+        let skiplist = SharedBuffer::default();
+        let mut serializer = PositionSerializer::new(stream.clone(), skiplist.clone());
+        let mut offset = 0usize;
+        for &chunk_len in write_all_chunks {
+            serializer.write_all(&vals[offset..offset + chunk_len]).unwrap();
+            offset += chunk_len;
+        }
+        serializer.write_all(&vals[offset..]).unwrap();
+        serializer.close().unwrap();
+        (stream.0.borrow().clone(), skiplist.0.borrow().clone())
+    }
+
+    #[test]
+    fn test_write_all_less_than_one_block() {
+        let vals: Vec<u32> = (0..50u32).collect();
+        assert_eq!(serialize_write_all(&vals, &[]), serialize_one_at_a_time(&vals));
+    }
+
+    #[test]
+    fn test_write_all_exactly_one_block() {
+        let vals: Vec<u32> = (0..COMPRESSION_BLOCK_SIZE as u32).collect();
+        assert_eq!(serialize_write_all(&vals, &[]), serialize_one_at_a_time(&vals));
+    }
+
+    #[test]
+    fn test_write_all_several_blocks_in_one_call() {
+        let vals: Vec<u32> = (0..(COMPRESSION_BLOCK_SIZE * 3 + 40) as u32).collect();
+        assert_eq!(serialize_write_all(&vals, &[]), serialize_one_at_a_time(&vals));
+    }
+
+    #[test]
+    fn test_write_all_partial_fill_then_full_blocks_then_tail() {
+        let vals: Vec<u32> = (0..(COMPRESSION_BLOCK_SIZE * 3 + 40) as u32).collect();
+        // The first two calls only partially fill a fresh block (20, then 90 values), the final
+        // call then has to finish that block, bulk-compress the whole ones in its slice and
+        // stash the tail.
+        let chunks = &[20, 70][..];
+        assert_eq!(
+            serialize_write_all(&vals, chunks),
+            serialize_one_at_a_time(&vals)
+        );
+    }
+
+    #[test]
+    fn test_write_all_after_partial_write_calls() {
+        let vals: Vec<u32> = (0..(COMPRESSION_BLOCK_SIZE * 2 + 5) as u32).collect();
+        let stream = SharedBuffer::default();
+        let skiplist = SharedBuffer::default();
+        let mut serializer = PositionSerializer::new(stream.clone(), skiplist.clone());
+        for &val in &vals[..20] {
+            serializer.write(val).unwrap();
+        }
+        serializer.write_all(&vals[20..]).unwrap();
+        serializer.close().unwrap();
+        let got = (stream.0.borrow().clone(), skiplist.0.borrow().clone());
+        assert_eq!(got, serialize_one_at_a_time(&vals));
+    }
+}